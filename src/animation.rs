@@ -0,0 +1,66 @@
+use std::f32::consts::TAU;
+
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+const DIRECTIONS: usize = 8;
+
+// Sprite sheet with one row per direction, starting east and going
+// counter-clockwise, with `frames_per_direction` frames in each row.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct DirectionalSprite {
+    pub frames_per_direction: usize,
+}
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<DirectionalSprite>()
+        .add_systems(Update, update_directional_sprites);
+}
+
+// Buckets a velocity into one of the eight directions, 0 being east.
+pub fn direction_index(velocity: Vec2) -> usize {
+    let sector = velocity.to_angle() / (TAU / DIRECTIONS as f32);
+    (sector.round() as i32).rem_euclid(DIRECTIONS as i32) as usize
+}
+
+fn update_directional_sprites(
+    mut sprites: Query<(&DirectionalSprite, &LinearVelocity, &mut Sprite)>,
+) {
+    for (directional, velocity, mut sprite) in &mut sprites {
+        // Keep facing the last direction when standing still.
+        if velocity.length_squared() < f32::EPSILON {
+            continue;
+        }
+
+        let Some(atlas) = sprite.texture_atlas.as_mut() else {
+            continue;
+        };
+
+        let frames = directional.frames_per_direction.max(1);
+        let frame = atlas.index % frames;
+        atlas.index = direction_index(velocity.0) * frames + frame;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn direction_index_buckets_velocity() {
+        assert_eq!(direction_index(Vec2::new(1.0, 0.0)), 0);
+        assert_eq!(direction_index(Vec2::new(1.0, 1.0)), 1);
+        assert_eq!(direction_index(Vec2::new(0.0, 1.0)), 2);
+        assert_eq!(direction_index(Vec2::new(-1.0, 0.0)), 4);
+        assert_eq!(direction_index(Vec2::new(0.0, -1.0)), 6);
+        assert_eq!(direction_index(Vec2::new(1.0, -1.0)), 7);
+    }
+
+    #[test]
+    fn direction_index_rounds_to_nearest_bucket() {
+        // Just short of north-east still counts as east.
+        assert_eq!(direction_index(Vec2::from_angle(0.3)), 0);
+        assert_eq!(direction_index(Vec2::from_angle(0.5)), 1);
+    }
+}
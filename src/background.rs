@@ -0,0 +1,119 @@
+use bevy::prelude::*;
+
+use crate::{
+    camera::{follow_target, MainCamera},
+    render_layer::RenderLayerZ,
+};
+
+#[derive(Clone, Debug, Reflect)]
+pub struct ParallaxLayer {
+    // Asset path of the layer image, relative to the `assets` folder.
+    pub image: String,
+    // Fraction of the camera movement the layer follows: 0 stays fixed in the
+    // world, 1 sticks to the camera like a far away sky.
+    pub factor: f32,
+}
+
+// Layers drawn behind everything else, farthest first. Insert it before the
+// plugin runs, the background is spawned once on startup.
+#[derive(Resource, Default, Clone, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct Background {
+    pub layers: Vec<ParallaxLayer>,
+}
+
+#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct Parallax {
+    pub factor: f32,
+}
+
+impl Parallax {
+    // Where the layer sits for a given camera position.
+    pub fn offset(&self, camera: Vec2) -> Vec2 {
+        camera * self.factor
+    }
+}
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Background>()
+        .register_type::<Parallax>()
+        .init_resource::<Background>()
+        .add_systems(Startup, spawn_background)
+        .add_systems(
+            PostUpdate,
+            follow_camera
+                .after(follow_target)
+                .before(TransformSystem::TransformPropagate),
+        );
+}
+
+fn spawn_background(
+    mut commands: Commands,
+    background: Res<Background>,
+    asset_server: Res<AssetServer>,
+) {
+    for (index, layer) in background.layers.iter().enumerate() {
+        commands.spawn((
+            Name::new(format!("Background layer {index}")),
+            Sprite::from_image(asset_server.load(layer.image.clone())),
+            // Each following layer is drawn slightly closer.
+            Transform::from_xyz(0.0, 0.0, RenderLayerZ::Background.z() + index as f32),
+            Parallax {
+                factor: layer.factor,
+            },
+        ));
+    }
+}
+
+fn follow_camera(
+    cameras: Query<&Transform, (With<MainCamera>, Without<Parallax>)>,
+    mut layers: Query<(&Parallax, &mut Transform)>,
+) {
+    let Ok(camera) = cameras.get_single() else {
+        return;
+    };
+
+    for (parallax, mut transform) in &mut layers {
+        let offset = parallax.offset(camera.translation.truncate());
+        transform.translation.x = offset.x;
+        transform.translation.y = offset.y;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parallax_offset_is_a_fraction_of_the_camera_position() {
+        let parallax = Parallax { factor: 0.25 };
+
+        assert_eq!(
+            parallax.offset(Vec2::new(100.0, -40.0)),
+            Vec2::new(25.0, -10.0)
+        );
+        assert_eq!(Parallax { factor: 0.0 }.offset(Vec2::ONE), Vec2::ZERO);
+    }
+
+    #[test]
+    fn layers_follow_the_camera_by_their_factor() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_systems(Update, follow_camera);
+        app.world_mut()
+            .spawn((MainCamera, Transform::from_xyz(200.0, 80.0, 0.0)));
+        let layer = app
+            .world_mut()
+            .spawn((
+                Parallax { factor: 0.5 },
+                Transform::from_xyz(0.0, 0.0, -10.0),
+            ))
+            .id();
+
+        app.update();
+
+        let transform = app.world().get::<Transform>(layer).unwrap();
+        assert_eq!(transform.translation, Vec3::new(100.0, 40.0, -10.0));
+    }
+}
@@ -1,13 +1,462 @@
-use bevy::prelude::*;
+use avian2d::prelude::*;
+use bevy::{
+    core_pipeline::fxaa::Fxaa,
+    prelude::*,
+    window::{PrimaryWindow, WindowResized},
+};
 
-#[derive(Component)]
-#[require(Camera2d)]
+// How quickly the camera catches up with its target, higher is snappier.
+const FOLLOW_SPEED: f32 = 5.0;
+// Pixels per second covered by `CameraEasing::Linear`.
+const LINEAR_FOLLOW_SPEED: f32 = 400.0;
+const ROTATION_SPEED: f32 = 5.0;
+// Longest step the spring integrates at once, longer frames are split up so a
+// stiff spring can't blow up on a hitch.
+const SPRING_MAX_STEP: f32 = 1.0 / 120.0;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+#[require(Camera2d, FollowVelocity)]
 pub struct MainCamera;
 
+// Velocity of the camera while easing with a spring.
+#[derive(Component, Default, Clone, Copy, Debug)]
+pub(crate) struct FollowVelocity(Vec2);
+
+// Snaps the orthographic projection so each world pixel covers a whole number
+// of screen pixels, showing at least `target_height` world pixels vertically.
+#[derive(Component, Reflect, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct PixelPerfect {
+    pub target_height: u32,
+}
+
+// Entity the main camera follows, `None` leaves the camera where it is.
+#[derive(Resource, Reflect, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[reflect(Resource)]
+pub struct CameraTarget(pub Option<Entity>);
+
+// Half extents of a box around the camera center the target can move in
+// without the camera following. Zero follows every movement.
+#[derive(Resource, Reflect, Default, Clone, Copy, Debug, PartialEq)]
+#[reflect(Resource)]
+pub struct FollowDeadzone(pub Vec2);
+
+impl FollowDeadzone {
+    // Where the camera needs to be for `target` to sit back inside the box.
+    pub fn goal(&self, camera: Vec2, target: Vec2) -> Vec2 {
+        let extents = self.0.abs();
+        let offset = target - camera;
+        target - offset.clamp(-extents, extents)
+    }
+}
+
+// How the camera moves towards its follow goal.
+#[derive(Resource, Reflect, Default, Clone, Copy, Debug, PartialEq)]
+#[reflect(Resource)]
+pub enum CameraEasing {
+    // Constant speed until the goal is reached.
+    Linear,
+    // Fast at first, slowing down on approach.
+    #[default]
+    EaseOut,
+    // Spring-damper, overshoots a little when underdamped.
+    Spring {
+        stiffness: f32,
+        damping: f32,
+    },
+}
+
+impl CameraEasing {
+    // Advances `position` towards `goal` by `delta` seconds.
+    pub fn step(self, position: Vec2, velocity: &mut Vec2, goal: Vec2, delta: f32) -> Vec2 {
+        match self {
+            Self::Linear => {
+                *velocity = Vec2::ZERO;
+                position.move_towards(goal, LINEAR_FOLLOW_SPEED * delta)
+            }
+            Self::EaseOut => {
+                *velocity = Vec2::ZERO;
+                // Frame rate independent exponential smoothing.
+                position.lerp(goal, 1.0 - (-FOLLOW_SPEED * delta).exp())
+            }
+            Self::Spring { stiffness, damping } => {
+                let mut position = position;
+                let mut remaining = delta;
+                while remaining > 0.0 {
+                    let step = remaining.min(SPRING_MAX_STEP);
+                    let acceleration = (goal - position) * stiffness - *velocity * damping;
+                    *velocity += acceleration * step;
+                    position += *velocity * step;
+                    remaining -= step;
+                }
+                position
+            }
+        }
+    }
+}
+
+// Target z-rotation of the main camera in radians, eased towards over time.
+#[derive(Resource, Reflect, Default, Clone, Copy, Debug, PartialEq)]
+#[reflect(Resource)]
+pub struct CameraRotation(pub f32);
+
+// What a camera clears its target to before drawing. Secondary cameras (UI,
+// minimap) drawing on top of the main view should use `None`.
+#[derive(Component, Reflect, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[reflect(Component)]
+pub enum CameraClear {
+    #[default]
+    Background,
+    None,
+}
+
+impl CameraClear {
+    pub fn clear_color(self) -> ClearColorConfig {
+        match self {
+            Self::Background => ClearColorConfig::Default,
+            Self::None => ClearColorConfig::None,
+        }
+    }
+}
+
+// Anti-aliasing applied to the main camera, changing it at runtime swaps the
+// camera components. Defaults to Bevy's own 4x MSAA.
+#[derive(Resource, Reflect, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[reflect(Resource)]
+pub enum AntiAliasConfig {
+    Off,
+    Msaa2,
+    #[default]
+    Msaa4,
+    Fxaa,
+}
+
+impl AntiAliasConfig {
+    pub fn msaa(self) -> Msaa {
+        match self {
+            Self::Msaa2 => Msaa::Sample2,
+            Self::Msaa4 => Msaa::Sample4,
+            Self::Off | Self::Fxaa => Msaa::Off,
+        }
+    }
+}
+
 pub(super) fn plugin(app: &mut App) {
-    app.add_systems(Startup, initialize_camera);
+    app.register_type::<MainCamera>()
+        .register_type::<PixelPerfect>()
+        .register_type::<CameraTarget>()
+        .register_type::<CameraClear>()
+        .register_type::<AntiAliasConfig>()
+        .register_type::<CameraRotation>()
+        .register_type::<FollowDeadzone>()
+        .register_type::<CameraEasing>()
+        .init_resource::<CameraTarget>()
+        .init_resource::<CameraRotation>()
+        .init_resource::<FollowDeadzone>()
+        .init_resource::<CameraEasing>()
+        .init_resource::<AntiAliasConfig>()
+        .add_systems(Startup, initialize_camera)
+        .add_systems(
+            Update,
+            apply_anti_aliasing.run_if(resource_changed::<AntiAliasConfig>),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                apply_camera_clear,
+                apply_pixel_perfect,
+                // Physics steps in `PostUpdate` unless `DeterministicMode` moves
+                // it to the fixed loop, which has already run by now. Either
+                // way this follows where physics put the target this frame.
+                (follow_target, rotate_camera)
+                    .after(PhysicsSet::Sync)
+                    .before(TransformSystem::TransformPropagate),
+            ),
+        );
 }
 
 fn initialize_camera(mut commands: Commands) {
     commands.spawn(MainCamera);
 }
+
+fn apply_anti_aliasing(
+    mut commands: Commands,
+    config: Res<AntiAliasConfig>,
+    cameras: Query<Entity, With<MainCamera>>,
+) {
+    for entity in &cameras {
+        let mut camera = commands.entity(entity);
+        camera.insert(config.msaa());
+
+        if *config == AntiAliasConfig::Fxaa {
+            camera.insert(Fxaa::default());
+        } else {
+            camera.remove::<Fxaa>();
+        }
+    }
+}
+
+fn apply_camera_clear(mut cameras: Query<(&CameraClear, &mut Camera), Changed<CameraClear>>) {
+    for (clear, mut camera) in &mut cameras {
+        camera.clear_color = clear.clear_color();
+    }
+}
+
+// Largest integer scale that still fits `target_height` into the window.
+pub fn pixel_perfect_scale(window_height: u32, target_height: u32) -> u32 {
+    (window_height / target_height.max(1)).max(1)
+}
+
+fn apply_pixel_perfect(
+    mut resized: EventReader<WindowResized>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut cameras: Query<(Ref<PixelPerfect>, &mut OrthographicProjection)>,
+) {
+    let window_resized = resized.read().count() > 0;
+
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+
+    for (pixel_perfect, mut projection) in &mut cameras {
+        if !window_resized && !pixel_perfect.is_changed() {
+            continue;
+        }
+
+        let scale = pixel_perfect_scale(window.physical_height(), pixel_perfect.target_height);
+        projection.scale = window.scale_factor() / scale as f32;
+    }
+}
+
+pub(crate) fn follow_target(
+    time: Res<Time>,
+    target: Res<CameraTarget>,
+    deadzone: Res<FollowDeadzone>,
+    easing: Res<CameraEasing>,
+    targets: Query<&Transform, Without<MainCamera>>,
+    mut cameras: Query<(&mut Transform, &mut FollowVelocity), With<MainCamera>>,
+) {
+    let target = target.0.and_then(|entity| targets.get(entity).ok());
+
+    for (mut camera, mut velocity) in &mut cameras {
+        // Don't carry spring momentum over to the next target.
+        let Some(target) = target else {
+            velocity.0 = Vec2::ZERO;
+            continue;
+        };
+
+        let current = camera.translation.truncate();
+        let goal = deadzone.goal(current, target.translation.truncate());
+        let position = easing.step(current, &mut velocity.0, goal, time.delta_secs());
+        camera.translation.x = position.x;
+        camera.translation.y = position.y;
+    }
+}
+
+fn rotate_camera(
+    time: Res<Time>,
+    rotation: Res<CameraRotation>,
+    mut cameras: Query<&mut Transform, With<MainCamera>>,
+) {
+    let goal = Quat::from_rotation_z(rotation.0);
+    let t = 1.0 - (-ROTATION_SPEED * time.delta_secs()).exp();
+
+    for mut camera in &mut cameras {
+        camera.rotation = camera.rotation.slerp(goal, t);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+
+    #[test]
+    fn pixel_perfect_scale_picks_largest_integer_fit() {
+        assert_eq!(pixel_perfect_scale(600, 180), 3);
+        assert_eq!(pixel_perfect_scale(720, 180), 4);
+    }
+
+    #[test]
+    fn pixel_perfect_scale_never_goes_below_one() {
+        assert_eq!(pixel_perfect_scale(100, 180), 1);
+    }
+
+    #[test]
+    fn pixel_perfect_scale_handles_zero_target() {
+        assert_eq!(pixel_perfect_scale(600, 0), 600);
+    }
+
+    fn follow_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                100,
+            )))
+            .init_resource::<CameraTarget>()
+            .init_resource::<FollowDeadzone>()
+            .init_resource::<CameraEasing>()
+            .add_systems(Update, follow_target);
+        app
+    }
+
+    #[test]
+    fn camera_follows_the_target_entity() {
+        let mut app = follow_app();
+        let camera = app.world_mut().spawn(MainCamera).id();
+        let target = app
+            .world_mut()
+            .spawn(Transform::from_xyz(100.0, -50.0, 0.0))
+            .id();
+        app.world_mut().resource_mut::<CameraTarget>().0 = Some(target);
+
+        for _ in 0..30 {
+            app.update();
+        }
+
+        let position = app.world().get::<Transform>(camera).unwrap().translation;
+        assert!(position.truncate().distance(Vec2::new(100.0, -50.0)) < 1.0);
+    }
+
+    #[test]
+    fn camera_stays_put_without_a_target() {
+        let mut app = follow_app();
+        let camera = app.world_mut().spawn(MainCamera).id();
+        app.world_mut()
+            .spawn(Transform::from_xyz(100.0, -50.0, 0.0));
+
+        for _ in 0..30 {
+            app.update();
+        }
+
+        let position = app.world().get::<Transform>(camera).unwrap().translation;
+        assert_eq!(position, Vec3::ZERO);
+    }
+
+    #[test]
+    fn anti_aliasing_config_swaps_camera_components() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<AntiAliasConfig>()
+            .add_systems(
+                Update,
+                apply_anti_aliasing.run_if(resource_changed::<AntiAliasConfig>),
+            );
+        let camera = app.world_mut().spawn(MainCamera).id();
+
+        app.update();
+        assert_eq!(app.world().get::<Msaa>(camera), Some(&Msaa::Sample4));
+        assert!(app.world().get::<Fxaa>(camera).is_none());
+
+        *app.world_mut().resource_mut::<AntiAliasConfig>() = AntiAliasConfig::Fxaa;
+        app.update();
+        assert_eq!(app.world().get::<Msaa>(camera), Some(&Msaa::Off));
+        assert!(app.world().get::<Fxaa>(camera).is_some());
+
+        *app.world_mut().resource_mut::<AntiAliasConfig>() = AntiAliasConfig::Msaa2;
+        app.update();
+        assert_eq!(app.world().get::<Msaa>(camera), Some(&Msaa::Sample2));
+        assert!(app.world().get::<Fxaa>(camera).is_none());
+    }
+
+    #[test]
+    fn secondary_camera_with_clear_none_keeps_the_main_view() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_systems(Update, apply_camera_clear);
+        let main = app
+            .world_mut()
+            .spawn((MainCamera, CameraClear::default()))
+            .id();
+        let minimap = app.world_mut().spawn((Camera2d, CameraClear::None)).id();
+
+        app.update();
+
+        let clear_color = |entity| app.world().get::<Camera>(entity).unwrap().clear_color;
+        assert!(matches!(clear_color(main), ClearColorConfig::Default));
+        assert!(matches!(clear_color(minimap), ClearColorConfig::None));
+    }
+
+    #[test]
+    fn deadzone_goal_holds_still_inside_the_box() {
+        let deadzone = FollowDeadzone(Vec2::new(50.0, 30.0));
+
+        assert_eq!(
+            deadzone.goal(Vec2::ZERO, Vec2::new(20.0, -10.0)),
+            Vec2::ZERO
+        );
+    }
+
+    #[test]
+    fn deadzone_goal_only_follows_the_overshoot() {
+        let deadzone = FollowDeadzone(Vec2::new(50.0, 30.0));
+
+        assert_eq!(
+            deadzone.goal(Vec2::ZERO, Vec2::new(80.0, -40.0)),
+            Vec2::new(30.0, -10.0)
+        );
+        assert_eq!(
+            FollowDeadzone::default().goal(Vec2::ZERO, Vec2::ONE),
+            Vec2::ONE
+        );
+    }
+
+    // Furthest x reached while easing from the origin to x = 100 for 5 seconds.
+    fn max_follow_x(easing: CameraEasing, delta: f32) -> f32 {
+        let goal = Vec2::new(100.0, 0.0);
+        let mut position = Vec2::ZERO;
+        let mut velocity = Vec2::ZERO;
+        let mut max_x = f32::MIN;
+        for _ in 0..(5.0 / delta) as usize {
+            position = easing.step(position, &mut velocity, goal, delta);
+            max_x = max_x.max(position.x);
+        }
+        max_x
+    }
+
+    #[test]
+    fn underdamped_spring_overshoots_where_ease_out_does_not() {
+        let spring = CameraEasing::Spring {
+            stiffness: 100.0,
+            damping: 5.0,
+        };
+
+        assert!(max_follow_x(spring, 1.0 / 60.0) > 100.0);
+        assert!(max_follow_x(CameraEasing::EaseOut, 1.0 / 60.0) <= 100.0);
+    }
+
+    #[test]
+    fn spring_stays_stable_on_long_frames() {
+        let spring = CameraEasing::Spring {
+            stiffness: 400.0,
+            damping: 10.0,
+        };
+
+        assert!(max_follow_x(spring, 0.25) < 200.0);
+    }
+
+    #[test]
+    fn losing_the_target_resets_follow_velocity() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(CameraTarget(None))
+            .init_resource::<FollowDeadzone>()
+            .init_resource::<CameraEasing>()
+            .add_systems(Update, follow_target);
+        let camera = app
+            .world_mut()
+            .spawn((MainCamera, FollowVelocity(Vec2::ONE)))
+            .id();
+
+        app.update();
+
+        assert_eq!(
+            app.world().get::<FollowVelocity>(camera).unwrap().0,
+            Vec2::ZERO
+        );
+    }
+}
@@ -1,6 +1,128 @@
+use std::collections::VecDeque;
+
 use bevy::app::App;
-use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
+use bevy::prelude::*;
+
+use crate::camera::MainCamera;
+
+const DEFAULT_HISTORY_LENGTH: usize = 120;
+const GRAPH_SIZE: Vec2 = Vec2::new(120.0, 40.0);
+const GRAPH_MARGIN: f32 = 8.0;
+// Frame time (ms) at the top of the graph, anything slower is clipped.
+const GRAPH_MAX_FRAME_TIME: f32 = 50.0;
+const TARGET_FRAME_TIME: f32 = 1000.0 / 60.0;
+
+// Last frame times in milliseconds, oldest first. Insert it before the plugin
+// runs to change how many frames are kept.
+#[derive(Resource, Debug, Clone)]
+pub struct FrameTimeHistory {
+    samples: VecDeque<f32>,
+    capacity: usize,
+}
+
+impl FrameTimeHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, frame_time: f32) {
+        self.samples.push_back(frame_time);
+        while self.samples.len() > self.capacity {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn samples(&self) -> impl Iterator<Item = &f32> {
+        self.samples.iter()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl Default for FrameTimeHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_HISTORY_LENGTH)
+    }
+}
 
 pub(super) fn plugin(app: &mut App) {
     app.add_plugins((LogDiagnosticsPlugin::default(), FrameTimeDiagnosticsPlugin));
+
+    // Plot recent frame times in the corner of the screen to spot hitches.
+    app.init_resource::<FrameTimeHistory>()
+        .add_systems(Update, (record_frame_time, draw_frame_time_graph).chain());
+}
+
+fn record_frame_time(diagnostics: Res<DiagnosticsStore>, mut history: ResMut<FrameTimeHistory>) {
+    let frame_time = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .and_then(|diagnostic| diagnostic.value());
+
+    if let Some(frame_time) = frame_time {
+        history.push(frame_time as f32);
+    }
+}
+
+fn draw_frame_time_graph(
+    mut gizmos: Gizmos,
+    history: Res<FrameTimeHistory>,
+    cameras: Query<(&GlobalTransform, &OrthographicProjection), With<MainCamera>>,
+) {
+    let Ok((transform, projection)) = cameras.get_single() else {
+        return;
+    };
+
+    // Pin the graph to the top left of the view, whatever the camera does. It
+    // is laid out in view space, then moved and rotated along with the camera.
+    let (_, rotation, translation) = transform.to_scale_rotation_translation();
+    let (angle, _, _) = rotation.to_euler(EulerRot::ZYX);
+    let view = Isometry2d::new(translation.truncate(), Rot2::radians(angle));
+
+    let size = GRAPH_SIZE * projection.scale;
+    let margin = GRAPH_MARGIN * projection.scale;
+    let top_left = Vec2::new(projection.area.min.x, projection.area.max.y);
+    let origin = top_left + Vec2::new(margin, -margin - size.y);
+
+    let step = size.x / history.capacity().max(1) as f32;
+    let points = history.samples().enumerate().map(|(index, frame_time)| {
+        let height = (frame_time / GRAPH_MAX_FRAME_TIME).min(1.0) * size.y;
+        view.transform_point(origin + Vec2::new(index as f32 * step, height))
+    });
+
+    gizmos.rect_2d(
+        view * Isometry2d::from_translation(origin + size / 2.0),
+        size,
+        Color::srgba(1.0, 1.0, 1.0, 0.3),
+    );
+
+    let target = origin.y + TARGET_FRAME_TIME / GRAPH_MAX_FRAME_TIME * size.y;
+    gizmos.line_2d(
+        view.transform_point(Vec2::new(origin.x, target)),
+        view.transform_point(Vec2::new(origin.x + size.x, target)),
+        Color::srgba(0.0, 1.0, 0.0, 0.5),
+    );
+
+    gizmos.linestrip_2d(points, Color::srgb(1.0, 0.8, 0.0));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_time_history_caps_and_drops_the_oldest() {
+        let mut history = FrameTimeHistory::new(3);
+        for frame_time in [10.0, 20.0, 30.0, 40.0, 50.0] {
+            history.push(frame_time);
+        }
+
+        let samples: Vec<f32> = history.samples().copied().collect();
+        assert_eq!(samples, [30.0, 40.0, 50.0]);
+    }
 }
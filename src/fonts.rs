@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+
+const DEFAULT_FONT_PATH: &str = "fonts/PixelSmall.ttf";
+
+// Insert before the plugin runs to swap the default font without touching
+// this file. An empty path falls back to the bundled font.
+#[derive(Resource, Reflect, Clone, Debug)]
+#[reflect(Resource)]
+pub struct FontConfig {
+    pub default_path: String,
+}
+
+impl Default for FontConfig {
+    fn default() -> Self {
+        Self {
+            default_path: DEFAULT_FONT_PATH.into(),
+        }
+    }
+}
+
+#[derive(Resource, Reflect, Clone, Debug)]
+#[reflect(Resource)]
+pub struct FontAssets {
+    pub default: Handle<Font>,
+}
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<FontConfig>()
+        .register_type::<FontAssets>()
+        .init_resource::<FontConfig>()
+        .add_systems(PreStartup, load_fonts);
+}
+
+fn load_fonts(mut commands: Commands, config: Res<FontConfig>, asset_server: Res<AssetServer>) {
+    let path = if config.default_path.is_empty() {
+        DEFAULT_FONT_PATH.to_owned()
+    } else {
+        config.default_path.clone()
+    };
+
+    commands.insert_resource(FontAssets {
+        default: asset_server.load(path),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::AssetPath;
+
+    use super::*;
+
+    fn loaded_font_path(config: FontConfig) -> Option<AssetPath<'static>> {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Font>()
+            .insert_resource(config)
+            .add_plugins(plugin);
+
+        app.update();
+
+        app.world().resource::<FontAssets>().default.path().cloned()
+    }
+
+    #[test]
+    fn loads_the_configured_font() {
+        let path = loaded_font_path(FontConfig {
+            default_path: "fonts/Custom.ttf".into(),
+        });
+
+        assert_eq!(path, Some(AssetPath::from("fonts/Custom.ttf")));
+    }
+
+    #[test]
+    fn empty_path_falls_back_to_the_bundled_font() {
+        let path = loaded_font_path(FontConfig {
+            default_path: String::new(),
+        });
+
+        assert_eq!(path, Some(AssetPath::from(DEFAULT_FONT_PATH)));
+    }
+}
@@ -1,9 +1,226 @@
-#![allow(unused)]
-use bevy::prelude::*;
+use bevy::{input::InputSystem, prelude::*, window::PrimaryWindow};
 
-#[derive(Resource)]
-pub struct MousePosition(Vec2);
+use crate::camera::MainCamera;
+
+// Cursor position in world coordinates, as seen through the main camera.
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct MousePosition(pub Vec2);
+
+// Time constant in seconds for easing `MousePosition` towards the cursor, which
+// takes the edge off jittery input. Zero follows the cursor exactly.
+#[derive(Resource, Reflect, Default, Clone, Copy, Debug, PartialEq)]
+#[reflect(Resource)]
+pub struct MouseSmoothing(pub f32);
+
+impl MouseSmoothing {
+    pub fn smooth(&self, current: Vec2, latest: Vec2, delta: f32) -> Vec2 {
+        if self.0 <= 0.0 {
+            return latest;
+        }
+
+        current.lerp(latest, 1.0 - (-delta / self.0).exp())
+    }
+}
+
+// Control scheme independent input, gameplay should read this rather than
+// raw keys. Both axes are at most unit length.
+#[derive(Resource, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Resource)]
+pub struct InputAxis {
+    pub movement: Vec2,
+    pub aim: Vec2,
+}
+
+// Stick values below this are treated as resting.
+const STICK_DEADZONE: f32 = 0.2;
+
+const UP_KEYS: [KeyCode; 2] = [KeyCode::KeyW, KeyCode::ArrowUp];
+const DOWN_KEYS: [KeyCode; 2] = [KeyCode::KeyS, KeyCode::ArrowDown];
+const LEFT_KEYS: [KeyCode; 2] = [KeyCode::KeyA, KeyCode::ArrowLeft];
+const RIGHT_KEYS: [KeyCode; 2] = [KeyCode::KeyD, KeyCode::ArrowRight];
 
 pub(super) fn plugin(app: &mut App) {
-    app.insert_resource(MousePosition(Vec2::default()));
+    app.register_type::<MousePosition>()
+        .register_type::<MouseSmoothing>()
+        .register_type::<InputAxis>()
+        .insert_resource(MousePosition(Vec2::default()))
+        .init_resource::<MouseSmoothing>()
+        .init_resource::<InputAxis>()
+        .add_systems(
+            PreUpdate,
+            (update_mouse_position, update_input_axis)
+                .chain()
+                .after(InputSystem),
+        );
+}
+
+// Goes through the camera's global transform, so zoom, panning and rotation are
+// all accounted for.
+fn update_mouse_position(
+    // Real time, so the cursor keeps tracking while the game is paused.
+    time: Res<Time<Real>>,
+    smoothing: Res<MouseSmoothing>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+    mut mouse_position: ResMut<MousePosition>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let Ok((camera, transform)) = cameras.get_single() else {
+        return;
+    };
+    let Some(cursor) = window.cursor_position() else {
+        return;
+    };
+
+    if let Ok(position) = camera.viewport_to_world_2d(transform, cursor) {
+        mouse_position.0 = smoothing.smooth(mouse_position.0, position, time.delta_secs());
+    }
+}
+
+fn update_input_axis(
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mouse_position: Res<MousePosition>,
+    cameras: Query<&GlobalTransform, With<MainCamera>>,
+    mut axis: ResMut<InputAxis>,
+) {
+    let pressed = |codes: [KeyCode; 2]| keys.any_pressed(codes) as i8 as f32;
+    let keyboard = Vec2::new(
+        pressed(RIGHT_KEYS) - pressed(LEFT_KEYS),
+        pressed(UP_KEYS) - pressed(DOWN_KEYS),
+    )
+    .normalize_or_zero();
+
+    // Aim at the cursor from the middle of the screen.
+    let mouse_aim = cameras
+        .get_single()
+        .map(|camera| (mouse_position.0 - camera.translation().truncate()).normalize_or_zero())
+        .unwrap_or_default();
+
+    axis.movement = keyboard;
+    axis.aim = mouse_aim;
+
+    // Any stick that is pushed takes over from keyboard and mouse.
+    for gamepad in &gamepads {
+        let left = gamepad.left_stick();
+        if left.length() > STICK_DEADZONE {
+            axis.movement = left.clamp_length_max(1.0);
+        }
+
+        let right = gamepad.right_stick();
+        if right.length() > STICK_DEADZONE {
+            axis.aim = right.normalize_or_zero();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_1_SQRT_2, FRAC_PI_2};
+
+    use bevy::{
+        math::DVec2,
+        render::camera::{camera_system, ManualTextureViews},
+        window::{WindowCreated, WindowResized, WindowResolution, WindowScaleFactorChanged},
+    };
+
+    use super::*;
+
+    #[test]
+    fn mouse_position_accounts_for_camera_rotation() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_event::<WindowCreated>()
+            .add_event::<WindowResized>()
+            .add_event::<WindowScaleFactorChanged>()
+            .add_event::<AssetEvent<Image>>()
+            .init_resource::<Assets<Image>>()
+            .init_resource::<ManualTextureViews>()
+            .insert_resource(MousePosition(Vec2::ZERO))
+            .init_resource::<MouseSmoothing>()
+            .add_systems(
+                Update,
+                (
+                    camera_system::<OrthographicProjection>,
+                    update_mouse_position,
+                )
+                    .chain(),
+            );
+
+        let mut window = Window {
+            resolution: WindowResolution::new(800.0, 600.0).with_scale_factor_override(1.0),
+            ..default()
+        };
+        // 200 pixels right of the middle of the screen.
+        window.set_physical_cursor_position(Some(DVec2::new(600.0, 300.0)));
+        app.world_mut().spawn((window, PrimaryWindow));
+
+        // Turned a quarter counterclockwise, so screen right points up the world.
+        let transform =
+            Transform::from_xyz(50.0, 0.0, 0.0).with_rotation(Quat::from_rotation_z(FRAC_PI_2));
+        app.world_mut()
+            .spawn((MainCamera, transform, GlobalTransform::from(transform)));
+
+        app.update();
+
+        let position = app.world().resource::<MousePosition>().0;
+        assert!(
+            position.distance(Vec2::new(50.0, 200.0)) < 1e-3,
+            "{position}"
+        );
+    }
+
+    #[test]
+    fn diagonal_keys_give_a_unit_movement_axis() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<ButtonInput<KeyCode>>()
+            .insert_resource(MousePosition(Vec2::ZERO))
+            .init_resource::<InputAxis>()
+            .add_systems(Update, update_input_axis);
+        let mut keys = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        keys.press(KeyCode::KeyW);
+        keys.press(KeyCode::KeyD);
+
+        app.update();
+
+        let movement = app.world().resource::<InputAxis>().movement;
+        assert!(
+            movement.distance(Vec2::splat(FRAC_1_SQRT_2)) < 1e-6,
+            "{movement}"
+        );
+    }
+
+    #[test]
+    fn mouse_smoothing_lags_behind_the_cursor() {
+        let smoothing = MouseSmoothing(0.1);
+        let cursor = Vec2::new(100.0, 0.0);
+
+        let mut smoothed = Vec2::ZERO;
+        let mut previous = 0.0;
+        for _ in 0..5 {
+            smoothed = smoothing.smooth(smoothed, cursor, 1.0 / 60.0);
+            assert!(smoothed.x > previous && smoothed.x < cursor.x);
+            previous = smoothed.x;
+        }
+
+        // Catches up once the cursor rests.
+        for _ in 0..120 {
+            smoothed = smoothing.smooth(smoothed, cursor, 1.0 / 60.0);
+        }
+        assert!(smoothed.distance(cursor) < 1e-3);
+    }
+
+    #[test]
+    fn zero_mouse_smoothing_follows_the_cursor_exactly() {
+        let cursor = Vec2::new(100.0, -20.0);
+
+        assert_eq!(
+            MouseSmoothing(0.0).smooth(Vec2::ZERO, cursor, 1.0 / 60.0),
+            cursor
+        );
+    }
 }
@@ -1,11 +1,20 @@
 use bevy::prelude::*;
 
+mod animation;
+mod background;
 mod camera;
 mod debug;
 mod dev_tools;
+mod fonts;
 mod game;
 mod input;
 mod physics;
+mod render_layer;
+mod spawner;
+#[cfg(feature = "spectator")]
+mod spectator;
+mod timers;
+mod trail;
 mod utils;
 mod window;
 
@@ -16,8 +25,13 @@ impl Plugin for AppPlugin {
         app.add_plugins((
             window::plugin,
             camera::plugin,
+            background::plugin,
             physics::plugin,
             input::plugin,
+            fonts::plugin,
+            timers::plugin,
+            animation::plugin,
+            trail::plugin,
             game::plugin,
         ));
 
@@ -27,5 +41,41 @@ impl Plugin for AppPlugin {
             dev_tools::plugin,
             debug::plugin
         ));
+
+        // Open a second window for spectating.
+        #[cfg(feature = "spectator")]
+        app.add_plugins(spectator::plugin);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::TypeId;
+
+    use super::*;
+
+    #[test]
+    fn gameplay_types_are_reflected() {
+        let mut app = App::new();
+        app.add_plugins((
+            camera::plugin,
+            input::plugin,
+            timers::plugin,
+            animation::plugin,
+            trail::plugin,
+        ));
+
+        let registry = app.world().resource::<AppTypeRegistry>().read();
+        for type_id in [
+            TypeId::of::<camera::MainCamera>(),
+            TypeId::of::<camera::PixelPerfect>(),
+            TypeId::of::<input::MousePosition>(),
+            TypeId::of::<timers::Cooldown>(),
+            TypeId::of::<animation::DirectionalSprite>(),
+            TypeId::of::<trail::Trail>(),
+            TypeId::of::<trail::TrailConfig>(),
+        ] {
+            assert!(registry.contains(type_id));
+        }
     }
 }
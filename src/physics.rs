@@ -1,10 +1,796 @@
+use std::time::Duration;
+
 use avian2d::{math::*, prelude::*};
-use bevy::prelude::*;
+use bevy::{asset::LoadState, prelude::*, utils::HashMap};
+
+// Pixels at least this opaque count as solid when building sprite colliders.
+const ALPHA_THRESHOLD: u8 = 128;
+// Ball given to alpha collider sprites whose image fails to load, unless they
+// have a custom size.
+const FALLBACK_COLLIDER_RADIUS: f32 = 8.0;
+// Physics steps per second in `DeterministicMode`.
+const DETERMINISTIC_HZ: f64 = 64.0;
+
+// Solver settings, read once when the physics plugin is built. Raise the
+// substeps or enable CCD when fast bodies (bullets) tunnel through thin ones.
+#[derive(Resource, Reflect, Clone, Copy, Debug)]
+#[reflect(Resource)]
+pub struct PhysicsConfig {
+    pub substeps: u32,
+    // Adds swept continuous collision detection to every dynamic body.
+    pub ccd: bool,
+    // Caps how far ahead contacts are predicted. Avian's default is unbounded,
+    // scaled by velocity; keep it at least as large as the distance the fastest
+    // body covers in a step.
+    pub speculative_margin: Option<f32>,
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self {
+            substeps: 6,
+            ccd: false,
+            speculative_margin: None,
+        }
+    }
+}
+
+// Physics normally steps once per frame in `PostUpdate` by the frame's delta.
+// This steps it in `FixedPostUpdate` at a pinned rate instead, and stops
+// clamping long frames, so the same inputs give the same simulation regardless
+// of frame rate. Read once when the physics plugin is built.
+#[derive(Resource, Reflect, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[reflect(Resource)]
+pub struct DeterministicMode(pub bool);
+
+// Whether physics is stepping. Turning it off only stops `Time<Physics>`, so
+// the rest of the game (UI, animations) keeps running, e.g. during cutscenes.
+#[derive(Resource, Reflect, Clone, Copy, Debug, PartialEq, Eq)]
+#[reflect(Resource)]
+pub struct PhysicsActive(pub bool);
+
+impl Default for PhysicsActive {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+// Gives the entity a convex collider wrapping the opaque pixels of its sprite
+// once the image has loaded. It matches how the sprite is drawn: only its atlas
+// tile or `rect`, stretched to its `custom_size`, flipped and moved by its
+// anchor. Falls back to a ball when nothing is opaque or the image fails to
+// load.
+#[derive(Component, Reflect, Default, Clone, Copy, Debug)]
+#[reflect(Component)]
+pub struct AlphaCollider;
+
+// Outlines built from sprite alpha, so each texture region is only scanned
+// once.
+#[derive(Resource, Default)]
+pub struct SpriteColliderCache(HashMap<(AssetId<Image>, URect), Option<Vec<Vector>>>);
 
 pub(super) fn plugin(app: &mut App) {
+    app.register_type::<PhysicsConfig>()
+        .register_type::<DeterministicMode>()
+        .register_type::<AlphaCollider>()
+        .register_type::<PhysicsActive>()
+        .init_resource::<PhysicsConfig>()
+        .init_resource::<DeterministicMode>()
+        .init_resource::<SpriteColliderCache>()
+        .init_resource::<PhysicsActive>();
+
+    let config = *app.world().resource::<PhysicsConfig>();
+    let deterministic = *app.world().resource::<DeterministicMode>();
+
+    let physics_plugins = if deterministic.0 {
+        // Virtual time would drop anything past its max delta on a long frame,
+        // losing fixed steps.
+        app.insert_resource(Time::<Fixed>::from_hz(DETERMINISTIC_HZ))
+            .add_systems(Startup, unclamp_virtual_time);
+        PhysicsPlugins::new(FixedPostUpdate)
+    } else {
+        PhysicsPlugins::new(PostUpdate)
+    };
+
     // Add physics plugins and specify a units-per-meter scaling factor, 1 meter = 20 pixels. The
     // unit allows the engine to tune its parameters for the scale of the world, improving
     // stability.
-    app.add_plugins(PhysicsPlugins::default().with_length_unit(20.0))
-        .insert_resource(Gravity(Vector::NEG_Y * 1000.0));
+    app.add_plugins(physics_plugins.with_length_unit(20.0))
+        .insert_resource(Gravity(Vector::NEG_Y * 1000.0))
+        .insert_resource(SubstepCount(config.substeps))
+        .add_systems(Update, insert_alpha_colliders)
+        // Ahead of the physics step, so it stops on the frame it's turned off.
+        .add_systems(
+            PreUpdate,
+            apply_physics_active.run_if(resource_changed::<PhysicsActive>),
+        );
+
+    if config.ccd {
+        app.add_systems(Update, enable_ccd);
+    }
+
+    if let Some(margin) = config.speculative_margin {
+        app.insert_resource(NarrowPhaseConfig {
+            default_speculative_margin: margin,
+            ..default()
+        });
+    }
+}
+
+fn unclamp_virtual_time(mut time: ResMut<Time<Virtual>>) {
+    time.set_max_delta(Duration::MAX);
+}
+
+fn apply_physics_active(active: Res<PhysicsActive>, mut time: ResMut<Time<Physics>>) {
+    if active.0 {
+        time.unpause();
+    } else {
+        time.pause();
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn enable_ccd(
+    mut commands: Commands,
+    bodies: Query<(Entity, &RigidBody), (Added<RigidBody>, Without<SweptCcd>)>,
+) {
+    for (entity, rigid_body) in &bodies {
+        if rigid_body.is_dynamic() {
+            commands.entity(entity).insert(SweptCcd::default());
+        }
+    }
+}
+
+// Corners of the convex hull around the opaque pixels of `region` in `image`,
+// in pixels from the center of the region with y up. `None` when the format
+// can't be read or nothing is opaque.
+pub fn alpha_outline(image: &Image, region: URect) -> Option<Vec<Vector>> {
+    let rgba = image.clone().try_into_dynamic().ok()?.into_rgba8();
+    let (width, height) = rgba.dimensions();
+    let region = region.intersect(URect::new(0, 0, width, height));
+    if region.is_empty() {
+        return None;
+    }
+
+    let half_size = region.size().as_vec2() / 2.0;
+
+    let points: Vec<Vector> = rgba
+        .enumerate_pixels()
+        .filter(|(x, y, pixel)| {
+            // `max` is the first column and row of the neighbouring tile.
+            (region.min.x..region.max.x).contains(x)
+                && (region.min.y..region.max.y).contains(y)
+                && pixel.0[3] >= ALPHA_THRESHOLD
+        })
+        .flat_map(|(x, y, _)| {
+            // Image rows go down while world y goes up.
+            let (x, y) = ((x - region.min.x) as f32, (y - region.min.y) as f32);
+            let corner = Vec2::new(x - half_size.x, half_size.y - y);
+            [
+                corner,
+                corner + Vec2::X,
+                corner - Vec2::Y,
+                corner + Vec2::new(1.0, -1.0),
+            ]
+        })
+        .collect();
+
+    if points.is_empty() {
+        return None;
+    }
+
+    let hull = Collider::convex_hull(points)?;
+    let polygon = hull.shape().as_convex_polygon()?;
+    Some(
+        polygon
+            .points()
+            .iter()
+            .map(|point| Vector::new(point.x, point.y))
+            .collect(),
+    )
+}
+
+// Fits an outline of a `region_size` pixel region to `sprite` drawn at `size`.
+fn sprite_collider(
+    outline: &[Vector],
+    sprite: &Sprite,
+    region_size: Vec2,
+    size: Vec2,
+) -> Option<Collider> {
+    let flip = Vec2::new(
+        if sprite.flip_x { -1.0 } else { 1.0 },
+        if sprite.flip_y { -1.0 } else { 1.0 },
+    );
+    let scale = size / region_size * flip;
+    // The anchor is the point of the sprite that sits on the entity.
+    let offset = -sprite.anchor.as_vec() * size;
+
+    Collider::convex_hull(
+        outline
+            .iter()
+            .map(|&point| point * scale + offset)
+            .collect(),
+    )
+}
+
+#[allow(clippy::type_complexity)]
+fn insert_alpha_colliders(
+    mut commands: Commands,
+    mut cache: ResMut<SpriteColliderCache>,
+    images: Res<Assets<Image>>,
+    layouts: Res<Assets<TextureAtlasLayout>>,
+    asset_server: Res<AssetServer>,
+    sprites: Query<(Entity, &Sprite), (With<AlphaCollider>, Without<Collider>)>,
+) {
+    for (entity, sprite) in &sprites {
+        let id = sprite.image.id();
+        let Some(image) = images.get(id) else {
+            if let LoadState::Failed(error) = asset_server.load_state(id) {
+                warn!("Failed to load alpha collider image, using a ball: {error}");
+                let radius = sprite
+                    .custom_size
+                    .map_or(FALLBACK_COLLIDER_RADIUS, |size| size.min_element() / 2.0);
+                commands.entity(entity).insert(Collider::circle(radius));
+            }
+            continue;
+        };
+
+        // Same region and size the sprite is drawn with.
+        let region = match &sprite.texture_atlas {
+            Some(atlas) => {
+                let Some(rect) = atlas.texture_rect(&layouts) else {
+                    continue;
+                };
+                rect
+            }
+            None => sprite
+                .rect
+                .map(|rect| rect.as_urect())
+                .unwrap_or_else(|| URect::from_corners(UVec2::ZERO, image.size())),
+        };
+        let region_size = region.size().as_vec2();
+        let size = sprite.custom_size.unwrap_or(region_size);
+
+        let outline = cache
+            .0
+            .entry((id, region))
+            .or_insert_with(|| alpha_outline(image, region));
+
+        let collider = outline
+            .as_deref()
+            .and_then(|outline| sprite_collider(outline, sprite, region_size, size))
+            .unwrap_or_else(|| Collider::circle(size.min_element() / 2.0));
+
+        commands.entity(entity).insert(collider);
+    }
+}
+
+// First entity hit by a ray from `origin` towards `direction`, ignoring
+// anything further than `max_distance` or rejected by `filter`. A ray starting
+// inside a collider hits it straight away, so exclude the caster when casting
+// from inside one.
+#[allow(dead_code)]
+pub fn cast_ray(
+    spatial_query: &SpatialQuery,
+    origin: Vec2,
+    direction: Vec2,
+    max_distance: f32,
+    filter: &SpatialQueryFilter,
+) -> Option<Entity> {
+    let direction = Dir2::new(direction).ok()?;
+
+    spatial_query
+        .cast_ray(origin, direction, max_distance, true, filter)
+        .map(|hit| hit.entity)
+}
+
+// Whether nothing blocks the straight line between `from` and `to`, other
+// than the entities in `ignore` (usually the viewer and its target).
+#[allow(dead_code)]
+pub fn line_of_sight(
+    spatial_query: &SpatialQuery,
+    from: Vec2,
+    to: Vec2,
+    ignore: impl IntoIterator<Item = Entity>,
+) -> bool {
+    let Ok(direction) = Dir2::new(to - from) else {
+        return true;
+    };
+    let filter = SpatialQueryFilter::default().with_excluded_entities(ignore);
+
+    spatial_query
+        .cast_ray(from, direction, from.distance(to), true, &filter)
+        .is_none()
+}
+
+// Collider containing `point`, e.g. what is under the cursor. When several
+// overlap the lowest entity wins, so the pick doesn't flicker between frames.
+#[allow(dead_code)]
+pub fn entity_at_point(spatial_query: &SpatialQuery, point: Vec2) -> Option<Entity> {
+    spatial_query
+        .point_intersections(point, &SpatialQueryFilter::default())
+        .into_iter()
+        .min()
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        ecs::system::RunSystemOnce,
+        render::{
+            render_asset::RenderAssetUsages,
+            render_resource::{Extent3d, TextureDimension, TextureFormat},
+        },
+        time::TimeUpdateStrategy,
+    };
+
+    use super::*;
+
+    // Everything the physics plugin needs to run without a window, advancing
+    // 1/60 s of virtual time per update (the first update doesn't advance).
+    fn headless_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            HierarchyPlugin,
+            AssetPlugin::default(),
+            bevy::scene::ScenePlugin,
+        ))
+        .init_asset::<Image>()
+        .init_asset::<TextureAtlasLayout>()
+        .init_resource::<Assets<Mesh>>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1.0 / 60.0,
+        )));
+        app
+    }
+
+    fn physics_app(config: PhysicsConfig) -> App {
+        let mut app = headless_app();
+        app.insert_resource(config).add_plugins(plugin);
+        app
+    }
+
+    // 8x8 image, opaque only in the block spanning columns 2..6 of the top four
+    // rows.
+    fn block_image() -> Image {
+        let mut data = Vec::new();
+        for y in 0..8 {
+            for x in 0..8 {
+                let alpha = if (2..6).contains(&x) && y < 4 { 255 } else { 0 };
+                data.extend([255, 255, 255, alpha]);
+            }
+        }
+        Image::new(
+            Extent3d {
+                width: 8,
+                height: 8,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::all(),
+        )
+    }
+
+    fn bounds(collider: &Collider) -> (Vector, Vector) {
+        let aabb = collider.aabb(Vector::ZERO, 0.0);
+        (aabb.min, aabb.max)
+    }
+
+    // Drops a few bouncing balls on the ground for two seconds, `frame` at a
+    // time, and returns where they end up.
+    fn simulate(deterministic: bool, frame: Duration) -> Vec<Vector> {
+        let mut app = headless_app();
+        app.insert_resource(DeterministicMode(deterministic))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(frame))
+            .add_plugins(plugin);
+        app.world_mut().spawn((
+            RigidBody::Static,
+            Collider::rectangle(400.0, 20.0),
+            Transform::from_xyz(0.0, -100.0, 0.0),
+        ));
+        let balls: Vec<Entity> = (0..5)
+            .map(|index| {
+                let x = index as f32 * 15.0 - 30.0;
+                app.world_mut()
+                    .spawn((
+                        RigidBody::Dynamic,
+                        Collider::circle(8.0),
+                        Restitution::new(0.5),
+                        LinearVelocity(Vector::new(-x, 0.0)),
+                        Transform::from_xyz(x, index as f32 * 20.0, 0.0),
+                    ))
+                    .id()
+            })
+            .collect();
+
+        // One extra update since the first doesn't advance time.
+        let frames = Duration::from_secs(2).as_nanos() / frame.as_nanos();
+        for _ in 0..=frames {
+            app.update();
+        }
+
+        balls
+            .iter()
+            .map(|&ball| app.world().get::<Position>(ball).unwrap().0)
+            .collect()
+    }
+
+    #[test]
+    fn substep_count_matches_config() {
+        let app = physics_app(PhysicsConfig {
+            substeps: 12,
+            ..default()
+        });
+
+        assert_eq!(app.world().resource::<SubstepCount>().0, 12);
+    }
+
+    #[test]
+    fn ccd_is_added_to_dynamic_bodies_when_enabled() {
+        let mut app = physics_app(PhysicsConfig {
+            ccd: true,
+            ..default()
+        });
+        let dynamic = app.world_mut().spawn(RigidBody::Dynamic).id();
+        let fixed = app.world_mut().spawn(RigidBody::Static).id();
+
+        app.update();
+
+        assert!(app.world().get::<SweptCcd>(dynamic).is_some());
+        assert!(app.world().get::<SweptCcd>(fixed).is_none());
+    }
+
+    #[test]
+    fn ccd_is_left_off_by_default() {
+        let mut app = physics_app(PhysicsConfig::default());
+        let dynamic = app.world_mut().spawn(RigidBody::Dynamic).id();
+
+        app.update();
+
+        assert!(app.world().get::<SweptCcd>(dynamic).is_none());
+    }
+
+    // A viewer at the origin with two balls lined up to its right.
+    fn spatial_app() -> (App, [Entity; 3]) {
+        let mut app = physics_app(PhysicsConfig::default());
+        let [viewer, near, far] = [0.0, 100.0, 200.0].map(|x| {
+            app.world_mut()
+                .spawn((
+                    RigidBody::Static,
+                    Collider::circle(10.0),
+                    Transform::from_xyz(x, 0.0, 0.0),
+                ))
+                .id()
+        });
+        for _ in 0..3 {
+            app.update();
+        }
+        (app, [viewer, near, far])
+    }
+
+    #[test]
+    fn cast_ray_hits_the_first_collider_in_its_path() {
+        let (mut app, [viewer, near, _]) = spatial_app();
+
+        let cast = |direction: Vec2, max_distance: f32, filter: SpatialQueryFilter| {
+            move |spatial_query: SpatialQuery| {
+                cast_ray(&spatial_query, Vec2::ZERO, direction, max_distance, &filter)
+            }
+        };
+        let others = || SpatialQueryFilter::default().with_excluded_entities([viewer]);
+        let world = app.world_mut();
+        assert_eq!(
+            world
+                .run_system_once(cast(Vec2::X, 500.0, default()))
+                .unwrap(),
+            Some(viewer)
+        );
+        assert_eq!(
+            world
+                .run_system_once(cast(Vec2::X, 500.0, others()))
+                .unwrap(),
+            Some(near)
+        );
+        assert_eq!(
+            world
+                .run_system_once(cast(Vec2::X, 50.0, others()))
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            world
+                .run_system_once(cast(Vec2::Y, 500.0, others()))
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            world
+                .run_system_once(cast(Vec2::ZERO, 500.0, others()))
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn line_of_sight_is_blocked_by_colliders_in_between() {
+        let (mut app, [viewer, near, far]) = spatial_app();
+
+        let sees = |to: Vec2, target: Entity| {
+            move |spatial_query: SpatialQuery| {
+                line_of_sight(&spatial_query, Vec2::ZERO, to, [viewer, target])
+            }
+        };
+        let world = app.world_mut();
+        assert!(world
+            .run_system_once(sees(Vec2::new(100.0, 0.0), near))
+            .unwrap());
+        assert!(!world
+            .run_system_once(sees(Vec2::new(200.0, 0.0), far))
+            .unwrap());
+        assert!(world
+            .run_system_once(sees(Vec2::new(0.0, 200.0), viewer))
+            .unwrap());
+    }
+
+    #[test]
+    fn entity_at_point_picks_the_lowest_overlapping_entity() {
+        let (mut app, [_, near, _]) = spatial_app();
+        app.world_mut().spawn((
+            RigidBody::Static,
+            Collider::circle(10.0),
+            Transform::from_xyz(105.0, 0.0, 0.0),
+        ));
+        for _ in 0..3 {
+            app.update();
+        }
+
+        let pick =
+            |point: Vec2| move |spatial_query: SpatialQuery| entity_at_point(&spatial_query, point);
+        let world = app.world_mut();
+        assert_eq!(
+            world.run_system_once(pick(Vec2::new(103.0, 0.0))).unwrap(),
+            Some(near)
+        );
+        assert_eq!(
+            world.run_system_once(pick(Vec2::new(150.0, 0.0))).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn deterministic_mode_ignores_the_frame_rate() {
+        let expected = simulate(true, Duration::from_millis(20));
+
+        assert_ne!(expected[0], Vector::new(-30.0, 0.0));
+        // The long frames would be clamped by virtual time if left alone.
+        for frame in [50, 500] {
+            assert_eq!(simulate(true, Duration::from_millis(frame)), expected);
+        }
+    }
+
+    #[test]
+    fn physics_depends_on_the_frame_rate_by_default() {
+        assert_ne!(
+            simulate(false, Duration::from_millis(20)),
+            simulate(false, Duration::from_millis(50))
+        );
+    }
+
+    fn outline_bounds(outline: &[Vector]) -> (Vector, Vector) {
+        let min = outline.iter().copied().reduce(Vector::min).unwrap();
+        let max = outline.iter().copied().reduce(Vector::max).unwrap();
+        (min, max)
+    }
+
+    // Sprite showing `index` of `block_image` cut into four 4x4 tiles.
+    fn atlas_sprite(app: &mut App, index: usize) -> Sprite {
+        let image = app
+            .world_mut()
+            .resource_mut::<Assets<Image>>()
+            .add(block_image());
+        let layout = app
+            .world_mut()
+            .resource_mut::<Assets<TextureAtlasLayout>>()
+            .add(TextureAtlasLayout::from_grid(
+                UVec2::splat(4),
+                2,
+                2,
+                None,
+                None,
+            ));
+        Sprite {
+            image,
+            texture_atlas: Some(TextureAtlas { layout, index }),
+            ..default()
+        }
+    }
+
+    fn alpha_collider(app: &mut App, sprite: Sprite) -> Collider {
+        let entity = app.world_mut().spawn((AlphaCollider, sprite)).id();
+        app.update();
+        app.world().get::<Collider>(entity).unwrap().clone()
+    }
+
+    #[test]
+    fn alpha_outline_wraps_the_opaque_pixels() {
+        let outline = alpha_outline(&block_image(), URect::new(0, 0, 8, 8)).unwrap();
+
+        assert_eq!(
+            outline_bounds(&outline),
+            (Vector::new(-2.0, 0.0), Vector::new(2.0, 4.0))
+        );
+    }
+
+    #[test]
+    fn alpha_outline_stays_inside_its_region() {
+        // The opaque block carries on into the next tile at column 4.
+        let outline = alpha_outline(&block_image(), URect::new(0, 0, 4, 4)).unwrap();
+
+        assert_eq!(
+            outline_bounds(&outline),
+            (Vector::new(0.0, -2.0), Vector::new(2.0, 2.0))
+        );
+    }
+
+    #[test]
+    fn alpha_outline_is_none_for_a_transparent_region() {
+        assert!(alpha_outline(&block_image(), URect::new(0, 4, 8, 8)).is_none());
+    }
+
+    #[test]
+    fn alpha_collider_follows_the_atlas_tile_and_custom_size() {
+        let mut app = physics_app(PhysicsConfig::default());
+        // Top right tile, only its left half is opaque.
+        let sprite = Sprite {
+            custom_size: Some(Vec2::splat(8.0)),
+            ..atlas_sprite(&mut app, 1)
+        };
+
+        let collider = alpha_collider(&mut app, sprite);
+
+        assert!(collider.shape().as_ball().is_none());
+        assert_eq!(
+            bounds(&collider),
+            (Vector::new(-4.0, -4.0), Vector::new(0.0, 4.0))
+        );
+    }
+
+    #[test]
+    fn alpha_collider_is_flipped_and_anchored_like_the_sprite() {
+        let mut app = physics_app(PhysicsConfig::default());
+        // Top left tile, only its right half is opaque.
+        let sprite = Sprite {
+            flip_x: true,
+            anchor: bevy::sprite::Anchor::BottomLeft,
+            ..atlas_sprite(&mut app, 0)
+        };
+
+        let collider = alpha_collider(&mut app, sprite);
+
+        assert_eq!(
+            bounds(&collider),
+            (Vector::new(0.0, 0.0), Vector::new(2.0, 4.0))
+        );
+    }
+
+    #[test]
+    fn alpha_collider_falls_back_to_a_ball_when_the_image_fails() {
+        let mut app = physics_app(PhysicsConfig::default());
+        let image = app
+            .world()
+            .resource::<AssetServer>()
+            .load("missing/alpha_collider.png");
+        let entity = app
+            .world_mut()
+            .spawn((AlphaCollider, Sprite::from_image(image)))
+            .id();
+
+        // Loading happens on another thread.
+        for _ in 0..200 {
+            app.update();
+            if app.world().get::<Collider>(entity).is_some() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        let collider = app.world().get::<Collider>(entity).unwrap();
+        let ball = collider.shape().as_ball().unwrap();
+        assert_eq!(ball.radius, FALLBACK_COLLIDER_RADIUS);
+    }
+
+    #[test]
+    fn speculative_margin_is_applied_to_the_narrow_phase() {
+        let app = physics_app(PhysicsConfig {
+            speculative_margin: Some(5.0),
+            ..default()
+        });
+
+        let config = app.world().resource::<NarrowPhaseConfig>();
+        assert_eq!(config.default_speculative_margin, 5.0);
+    }
+
+    #[test]
+    fn speculative_margin_defaults_to_avian() {
+        let app = physics_app(PhysicsConfig::default());
+
+        let config = app.world().resource::<NarrowPhaseConfig>();
+        assert_eq!(
+            config.default_speculative_margin,
+            NarrowPhaseConfig::default().default_speculative_margin
+        );
+    }
+
+    // Fires a fast ball at a thin wall and returns whether they collided and
+    // where the ball ended up.
+    fn shoot_at_wall(speculative_margin: f32) -> (bool, f32) {
+        let mut app = physics_app(PhysicsConfig {
+            speculative_margin: Some(speculative_margin),
+            ..default()
+        });
+        let wall = app
+            .world_mut()
+            .spawn((
+                RigidBody::Static,
+                Collider::rectangle(1.0, 400.0),
+                Transform::from_xyz(100.0, 0.0, 0.0),
+            ))
+            .id();
+        // About 52 px per step, far thicker than the wall.
+        let ball = app
+            .world_mut()
+            .spawn((
+                RigidBody::Dynamic,
+                Collider::circle(2.0),
+                LinearVelocity(Vector::new(3100.0, 0.0)),
+                Transform::default(),
+            ))
+            .id();
+
+        let mut collided = false;
+        for _ in 0..10 {
+            app.update();
+            collided |= app.world().resource::<Collisions>().contains(ball, wall);
+        }
+
+        let x = app.world().get::<Position>(ball).unwrap().x;
+        (collided, x)
+    }
+
+    #[test]
+    fn speculative_margin_stops_fast_bodies_tunnelling() {
+        let (collided, x) = shoot_at_wall(100.0);
+        assert!(collided);
+        assert!(x < 100.0, "ball passed the wall at x = {x}");
+
+        let (collided, x) = shoot_at_wall(0.0);
+        assert!(!collided);
+        assert!(x > 100.0, "ball stopped at x = {x}");
+    }
+
+    #[test]
+    fn inactive_physics_stops_physics_time_only() {
+        let mut app = physics_app(PhysicsConfig::default());
+        for _ in 0..3 {
+            app.update();
+        }
+        let physics_elapsed = app.world().resource::<Time<Physics>>().elapsed();
+        let virtual_elapsed = app.world().resource::<Time<Virtual>>().elapsed();
+        assert!(physics_elapsed > Duration::ZERO);
+
+        app.insert_resource(PhysicsActive(false));
+        for _ in 0..3 {
+            app.update();
+        }
+
+        assert_eq!(
+            app.world().resource::<Time<Physics>>().elapsed(),
+            physics_elapsed
+        );
+        assert!(app.world().resource::<Time<Virtual>>().elapsed() > virtual_elapsed);
+    }
 }
@@ -0,0 +1,44 @@
+// Depth of each kind of world entity, so draw order doesn't depend on whatever
+// z a spawn site happened to pick. Bevy UI is drawn on top of all of these.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RenderLayerZ {
+    Background,
+    Tiles,
+    Actors,
+    Projectiles,
+    Overlay,
+}
+
+impl RenderLayerZ {
+    // Layers are spaced widely enough to stack a few sub-layers inside one.
+    pub const fn z(self) -> f32 {
+        match self {
+            Self::Background => -100.0,
+            Self::Tiles => 0.0,
+            Self::Actors => 100.0,
+            Self::Projectiles => 200.0,
+            Self::Overlay => 300.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layers_stack_from_background_to_overlay() {
+        let layers = [
+            RenderLayerZ::Background,
+            RenderLayerZ::Tiles,
+            RenderLayerZ::Actors,
+            RenderLayerZ::Projectiles,
+            RenderLayerZ::Overlay,
+        ];
+
+        for pair in layers.windows(2) {
+            assert!(pair[0].z() < pair[1].z(), "{:?} >= {:?}", pair[0], pair[1]);
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use bevy::prelude::*;
+
+// Spawns `count` copies of `bundle` every time the timer finishes. If the
+// spawner has a transform the copies appear at its position, treating any
+// `Transform` in the bundle as an offset from it.
+#[allow(dead_code)]
+#[derive(Component)]
+pub struct Spawner<B: Bundle + Clone> {
+    pub timer: Timer,
+    pub bundle: B,
+    pub count: u32,
+}
+
+#[allow(dead_code)]
+impl<B: Bundle + Clone> Spawner<B> {
+    pub fn every(seconds: f32, bundle: B, count: u32) -> Self {
+        Self {
+            timer: Timer::from_seconds(seconds, TimerMode::Repeating),
+            bundle,
+            count,
+        }
+    }
+}
+
+// Systems are generic, so add this once per bundle type in use:
+// `app.add_plugins(spawner::plugin::<MyBundle>)`.
+#[allow(dead_code)]
+pub fn plugin<B: Bundle + Clone>(app: &mut App) {
+    app.add_systems(Update, tick_spawners::<B>);
+}
+
+fn tick_spawners<B: Bundle + Clone>(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut spawners: Query<(&mut Spawner<B>, Option<&GlobalTransform>)>,
+) {
+    for (mut spawner, global_transform) in &mut spawners {
+        spawner.timer.tick(time.delta());
+
+        // A long frame can finish a short interval more than once.
+        let spawns = spawner.timer.times_finished_this_tick() * spawner.count;
+        for _ in 0..spawns {
+            let mut entity = commands.spawn(spawner.bundle.clone());
+            if let Some(global_transform) = global_transform {
+                let origin = global_transform.translation();
+                entity.queue(move |mut entity: EntityWorldMut| {
+                    match entity.get_mut::<Transform>() {
+                        Some(mut transform) => transform.translation += origin,
+                        None => {
+                            entity.insert(Transform::from_translation(origin));
+                        }
+                    }
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+
+    #[derive(Component, Clone)]
+    struct Spawned;
+
+    fn set_frame_time(app: &mut App, millis: u64) {
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            millis,
+        )));
+    }
+
+    fn spawned(app: &mut App) -> usize {
+        app.world_mut()
+            .query::<&Spawned>()
+            .iter(app.world())
+            .count()
+    }
+
+    #[test]
+    fn spawns_count_entities_per_interval() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, plugin::<Spawned>));
+        set_frame_time(&mut app, 250);
+        app.world_mut().spawn(Spawner::every(0.5, Spawned, 3));
+
+        // The first update doesn't advance time.
+        app.update();
+        app.update();
+        assert_eq!(spawned(&mut app), 0);
+
+        app.update();
+        assert_eq!(spawned(&mut app), 3);
+
+        app.update();
+        app.update();
+        assert_eq!(spawned(&mut app), 6);
+
+        // One long frame covering two intervals spawns for both.
+        app.world_mut()
+            .resource_mut::<Time<Virtual>>()
+            .set_max_delta(Duration::from_secs(5));
+        set_frame_time(&mut app, 1000);
+        app.update();
+        assert_eq!(spawned(&mut app), 12);
+    }
+    #[test]
+    fn spawns_at_the_spawner_offset_by_the_bundle() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, plugin::<(Spawned, Transform)>));
+        set_frame_time(&mut app, 250);
+        app.world_mut().spawn((
+            Spawner::every(0.25, (Spawned, Transform::from_xyz(1.0, 0.0, 0.0)), 1),
+            GlobalTransform::from_xyz(10.0, 20.0, 0.0),
+        ));
+
+        app.update();
+        app.update();
+
+        let transform = app
+            .world_mut()
+            .query_filtered::<&Transform, With<Spawned>>()
+            .single(app.world());
+        assert_eq!(transform.translation, Vec3::new(11.0, 20.0, 0.0));
+    }
+}
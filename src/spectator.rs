@@ -0,0 +1,94 @@
+//! Second OS window showing a zoomed out view of the game, for streaming and
+//! debugging. This plugin is only enabled with the `spectator` feature.
+
+use bevy::{
+    prelude::*,
+    render::camera::RenderTarget,
+    window::{WindowRef, WindowResolution},
+};
+
+use crate::camera::{follow_target, MainCamera};
+
+// Units of world per screen pixel, relative to the main camera.
+const SPECTATOR_ZOOM: f32 = 2.0;
+
+#[derive(Component)]
+pub struct SpectatorWindow;
+
+#[derive(Component)]
+#[require(Camera2d)]
+pub struct SpectatorCamera;
+
+pub(crate) fn plugin(app: &mut App) {
+    app.add_systems(Startup, spawn_spectator).add_systems(
+        PostUpdate,
+        follow_main_camera
+            .after(follow_target)
+            .before(TransformSystem::TransformPropagate),
+    );
+}
+
+fn spawn_spectator(mut commands: Commands) {
+    let window = commands
+        .spawn((
+            SpectatorWindow,
+            Window {
+                title: "Spectator".into(),
+                resolution: WindowResolution::new(800., 600.),
+                ..default()
+            },
+        ))
+        .id();
+
+    commands.spawn((
+        SpectatorCamera,
+        Camera {
+            target: RenderTarget::Window(WindowRef::Entity(window)),
+            ..default()
+        },
+        OrthographicProjection {
+            scale: SPECTATOR_ZOOM,
+            ..OrthographicProjection::default_2d()
+        },
+    ));
+}
+
+// Stay centered on whatever the main camera is looking at.
+fn follow_main_camera(
+    main_cameras: Query<&Transform, (With<MainCamera>, Without<SpectatorCamera>)>,
+    mut spectators: Query<&mut Transform, With<SpectatorCamera>>,
+) {
+    let Ok(main_camera) = main_cameras.get_single() else {
+        return;
+    };
+
+    for mut spectator in &mut spectators {
+        spectator.translation.x = main_camera.translation.x;
+        spectator.translation.y = main_camera.translation.y;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spectator_window_and_camera_are_spawned() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, plugin));
+
+        app.update();
+
+        let world = app.world_mut();
+        let window = world
+            .query_filtered::<Entity, With<SpectatorWindow>>()
+            .single(world);
+        let camera = world
+            .query_filtered::<&Camera, With<SpectatorCamera>>()
+            .single(world);
+        assert!(matches!(
+            camera.target,
+            RenderTarget::Window(WindowRef::Entity(entity)) if entity == window
+        ));
+    }
+}
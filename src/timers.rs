@@ -0,0 +1,201 @@
+use bevy::{ecs::entity::Entities, prelude::*};
+
+// Reusable cooldown for anything that shouldn't happen more than once per
+// duration (firing, switching stance, abilities...).
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+pub struct Cooldown(pub Timer);
+
+#[allow(dead_code)]
+impl Cooldown {
+    pub fn seconds(duration: f32) -> Self {
+        let mut timer = Timer::from_seconds(duration, TimerMode::Once);
+        // Start out ready rather than waiting a full duration on spawn.
+        timer.tick(timer.duration());
+        Self(timer)
+    }
+
+    pub fn ready(&self) -> bool {
+        self.0.finished()
+    }
+
+    // Restarts the cooldown, usually right after the action it guards.
+    pub fn trigger(&mut self) {
+        self.0.reset();
+    }
+}
+
+// Despawns the entity once the timer finishes. Works for anything short lived:
+// projectiles, particles, temporary UI.
+#[derive(Component, Reflect, Debug, Clone)]
+#[reflect(Component)]
+pub struct Lifetime(pub Timer);
+
+impl Lifetime {
+    #[allow(dead_code)]
+    pub fn seconds(duration: f32) -> Self {
+        Self(Timer::from_seconds(duration, TimerMode::Once))
+    }
+}
+
+// Above this many entities a warning is logged and the oldest short lived
+// entities (with a `Lifetime`) are despawned early to protect the frame rate.
+#[derive(Resource, Reflect, Clone, Copy, Debug)]
+#[reflect(Resource)]
+pub struct EntityCeiling {
+    pub threshold: usize,
+}
+
+impl Default for EntityCeiling {
+    fn default() -> Self {
+        Self { threshold: 10_000 }
+    }
+}
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<Cooldown>()
+        .register_type::<Lifetime>()
+        .register_type::<EntityCeiling>()
+        .init_resource::<EntityCeiling>()
+        .add_systems(PreUpdate, tick_cooldowns)
+        .add_systems(
+            PostUpdate,
+            (despawn_after_lifetime, enforce_entity_ceiling).chain(),
+        );
+}
+
+fn tick_cooldowns(time: Res<Time>, mut cooldowns: Query<&mut Cooldown>) {
+    for mut cooldown in &mut cooldowns {
+        cooldown.0.tick(time.delta());
+    }
+}
+
+fn despawn_after_lifetime(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut lifetimes: Query<(Entity, &mut Lifetime)>,
+) {
+    for (entity, mut lifetime) in &mut lifetimes {
+        if lifetime.0.tick(time.delta()).just_finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn enforce_entity_ceiling(
+    mut commands: Commands,
+    ceiling: Res<EntityCeiling>,
+    entities: &Entities,
+    temporary: Query<(Entity, &Lifetime), Without<Parent>>,
+    mut warned: Local<bool>,
+) {
+    let count = entities.len() as usize;
+    if count <= ceiling.threshold {
+        *warned = false;
+        return;
+    }
+
+    // Only warn once each time the ceiling is crossed.
+    if !*warned {
+        warn!(
+            "{count} entities exceed the ceiling of {}, culling short lived entities",
+            ceiling.threshold
+        );
+        *warned = true;
+    }
+
+    // Finished ones are already being despawned by `despawn_after_lifetime`.
+    let mut oldest: Vec<_> = temporary
+        .iter()
+        .filter(|(_, lifetime)| !lifetime.0.finished())
+        .collect();
+    oldest.sort_by_key(|(_, lifetime)| std::cmp::Reverse(lifetime.0.elapsed()));
+
+    for (entity, _) in oldest.into_iter().take(count - ceiling.threshold) {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+
+    #[test]
+    fn cooldown_is_ready_again_after_its_duration() {
+        let mut cooldown = Cooldown::seconds(1.0);
+        assert!(cooldown.ready());
+
+        cooldown.trigger();
+        assert!(!cooldown.ready());
+
+        cooldown.0.tick(Duration::from_millis(999));
+        assert!(!cooldown.ready());
+
+        cooldown.0.tick(Duration::from_millis(1));
+        assert!(cooldown.ready());
+    }
+
+    #[test]
+    fn lifetime_despawns_once_it_expires() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, plugin)).insert_resource(
+            TimeUpdateStrategy::ManualDuration(Duration::from_millis(200)),
+        );
+        let entity = app.world_mut().spawn(Lifetime::seconds(1.0)).id();
+
+        // The first update doesn't advance time, so this reaches 0.8 seconds.
+        for _ in 0..5 {
+            app.update();
+        }
+        assert!(app.world().get_entity(entity).is_ok());
+
+        app.update();
+        assert!(app.world().get_entity(entity).is_err());
+    }
+
+    #[test]
+    fn lifetime_freezes_while_virtual_time_is_paused() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, plugin)).insert_resource(
+            TimeUpdateStrategy::ManualDuration(Duration::from_millis(200)),
+        );
+        let entity = app.world_mut().spawn(Lifetime::seconds(10.0)).id();
+        app.update();
+        app.update();
+
+        app.world_mut().resource_mut::<Time<Virtual>>().pause();
+        for _ in 0..3 {
+            app.update();
+        }
+
+        let lifetime = app.world().get::<Lifetime>(entity).unwrap();
+        assert_eq!(lifetime.0.elapsed(), Duration::from_millis(200));
+    }
+
+    #[test]
+    fn entity_ceiling_culls_the_oldest_lifetimes() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, plugin));
+
+        let mut spawn_aged = |elapsed: f32| {
+            let mut lifetime = Lifetime::seconds(10.0);
+            lifetime.0.tick(Duration::from_secs_f32(elapsed));
+            app.world_mut().spawn(lifetime).id()
+        };
+        let oldest = spawn_aged(3.0);
+        let middle = spawn_aged(2.0);
+        let newest = spawn_aged(1.0);
+
+        let count = app.world().entities().len() as usize;
+        app.world_mut().resource_mut::<EntityCeiling>().threshold = count - 1;
+        app.update();
+
+        assert!(app.world().get_entity(oldest).is_err());
+        assert!(app.world().get_entity(middle).is_ok());
+        assert!(app.world().get_entity(newest).is_ok());
+    }
+}
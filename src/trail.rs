@@ -0,0 +1,114 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+const TRAIL_COLOR: Color = Color::WHITE;
+
+#[derive(Resource, Reflect, Clone, Copy, Debug)]
+#[reflect(Resource)]
+pub struct TrailConfig {
+    // Number of past positions kept per entity.
+    pub length: usize,
+    pub width: f32,
+}
+
+impl Default for TrailConfig {
+    fn default() -> Self {
+        Self {
+            length: 32,
+            width: 2.0,
+        }
+    }
+}
+
+// Records the recent positions of an entity, oldest first.
+#[derive(Component, Reflect, Default, Debug, Clone)]
+#[reflect(Component)]
+pub struct Trail {
+    points: VecDeque<Vec2>,
+}
+
+impl Trail {
+    pub fn push(&mut self, point: Vec2, length: usize) {
+        self.points.push_back(point);
+        while self.points.len() > length {
+            self.points.pop_front();
+        }
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = &Vec2> {
+        self.points.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+}
+
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub struct TrailGizmos;
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<TrailConfig>()
+        .register_type::<Trail>()
+        .init_resource::<TrailConfig>()
+        .init_gizmo_group::<TrailGizmos>()
+        .add_systems(
+            Update,
+            (
+                apply_trail_width.run_if(resource_changed::<TrailConfig>),
+                record_trails,
+                draw_trails,
+            )
+                .chain(),
+        );
+}
+
+fn apply_trail_width(config: Res<TrailConfig>, mut config_store: ResMut<GizmoConfigStore>) {
+    let (gizmo_config, _) = config_store.config_mut::<TrailGizmos>();
+    gizmo_config.line_width = config.width;
+}
+
+fn record_trails(config: Res<TrailConfig>, mut trails: Query<(&Transform, &mut Trail)>) {
+    for (transform, mut trail) in &mut trails {
+        trail.push(transform.translation.truncate(), config.length);
+    }
+}
+
+fn draw_trails(mut gizmos: Gizmos<TrailGizmos>, trails: Query<&Trail>) {
+    for trail in &trails {
+        let segments = trail.len().saturating_sub(1);
+
+        for (index, (start, end)) in trail.points().zip(trail.points().skip(1)).enumerate() {
+            // Fade out towards the oldest end of the trail.
+            let alpha = (index + 1) as f32 / segments as f32;
+            gizmos.line_2d(*start, *end, TRAIL_COLOR.with_alpha(alpha));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trail_caps_at_length_and_drops_oldest() {
+        let mut trail = Trail::default();
+
+        for x in 0..3 {
+            trail.push(Vec2::new(x as f32, 0.0), 3);
+        }
+        assert_eq!(trail.len(), 3);
+
+        trail.push(Vec2::new(3.0, 0.0), 3);
+        assert_eq!(trail.len(), 3);
+
+        let xs: Vec<f32> = trail.points().map(|point| point.x).collect();
+        assert_eq!(xs, [1.0, 2.0, 3.0]);
+    }
+}
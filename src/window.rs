@@ -1,20 +1,359 @@
-use bevy::prelude::*;
+use bevy::{
+    asset::LoadState,
+    input::common_conditions::input_just_pressed,
+    prelude::*,
+    window::{ExitCondition, PresentMode, PrimaryWindow, WindowResolution},
+    winit::{WinitSettings, WinitWindows},
+};
+use winit::window::Icon;
 
 const BACKGROUND_COLOR: Color = Color::srgb(0.4, 0.4, 0.4);
 
+const TOGGLE_VSYNC_KEY: KeyCode = KeyCode::F1;
+
+// Present mode of the primary window. Insert it before the plugin is built to
+// pick the starting mode, changing it at runtime updates the window.
+#[derive(Resource, Reflect, Clone, Copy, Debug, PartialEq, Eq)]
+#[reflect(Resource)]
+pub struct PresentModeConfig(pub PresentMode);
+
+impl Default for PresentModeConfig {
+    fn default() -> Self {
+        Self(PresentMode::AutoVsync)
+    }
+}
+
+impl PresentModeConfig {
+    pub fn toggle_vsync(&mut self) {
+        self.0 = match self.0 {
+            PresentMode::AutoVsync | PresentMode::Fifo | PresentMode::FifoRelaxed => {
+                PresentMode::Immediate
+            }
+            _ => PresentMode::AutoVsync,
+        };
+    }
+}
+
+// Title and icon of the primary window, read once when the plugin is built.
+#[derive(Resource, Reflect, Clone, Debug)]
+#[reflect(Resource)]
+pub struct WindowBranding {
+    pub title: String,
+    // Asset path of the icon, relative to the `assets` folder.
+    pub icon_path: Option<String>,
+}
+
+impl Default for WindowBranding {
+    fn default() -> Self {
+        Self {
+            title: "Bevy game".into(),
+            icon_path: None,
+        }
+    }
+}
+
+// `Reactive` only redraws on input or window events, which saves a lot of
+// CPU/GPU for mostly static scenes. Games with constant motion want `Continuous`.
+#[derive(Resource, Reflect, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[reflect(Resource)]
+pub enum RenderMode {
+    #[default]
+    Continuous,
+    Reactive,
+}
+
+impl RenderMode {
+    pub fn winit_settings(self) -> WinitSettings {
+        match self {
+            Self::Continuous => WinitSettings::game(),
+            Self::Reactive => WinitSettings::desktop_app(),
+        }
+    }
+}
+
+// Forces the window's scale factor instead of using the display's, e.g. `2.0`
+// to double everything on a HiDPI screen that would otherwise look tiny. The
+// logical size stays 800x600, so world coordinates don't change.
+#[derive(Resource, Reflect, Default, Clone, Copy, Debug, PartialEq)]
+#[reflect(Resource)]
+pub struct ScaleFactorOverride(pub Option<f32>);
+
+#[derive(Resource)]
+struct WindowIcon(Handle<Image>);
+
 pub(super) fn plugin(app: &mut App) {
-    let primary_window = Window {
-        title: "Bevy game".into(),
-        resizable: false,
-        resolution: (800., 600.).into(),
-        canvas: Some("#bevy".to_owned()),
-        desired_maximum_frame_latency: core::num::NonZero::new(1u32),
-        ..default()
+    init_config(app);
+
+    let primary_window = primary_window(app.world());
+
+    // Closing the main window quits the game, even with a spectator window open.
+    let exit_condition = if cfg!(feature = "spectator") {
+        ExitCondition::OnPrimaryClosed
+    } else {
+        ExitCondition::OnAllClosed
     };
 
     app.insert_resource(ClearColor(BACKGROUND_COLOR))
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(primary_window),
+            exit_condition,
             ..default()
         }));
+
+    app.add_systems(Startup, load_window_icon).add_systems(
+        Update,
+        (
+            (
+                toggle_vsync.run_if(input_just_pressed(TOGGLE_VSYNC_KEY)),
+                apply_present_mode.run_if(resource_changed::<PresentModeConfig>),
+            )
+                .chain(),
+            set_window_icon.run_if(resource_exists::<WindowIcon>),
+            apply_render_mode.run_if(resource_changed::<RenderMode>),
+            apply_scale_factor_override.run_if(resource_changed::<ScaleFactorOverride>),
+        ),
+    );
+}
+
+// Kept apart from the plugin, which can't be built without a display.
+fn init_config(app: &mut App) {
+    app.register_type::<PresentModeConfig>()
+        .register_type::<WindowBranding>()
+        .register_type::<RenderMode>()
+        .register_type::<ScaleFactorOverride>()
+        .init_resource::<PresentModeConfig>()
+        .init_resource::<WindowBranding>()
+        .init_resource::<RenderMode>()
+        .init_resource::<ScaleFactorOverride>();
+}
+
+// Primary window as described by the config resources.
+fn primary_window(world: &World) -> Window {
+    let present_mode = world.resource::<PresentModeConfig>().0;
+    let branding = world.resource::<WindowBranding>();
+    let scale_factor_override = world.resource::<ScaleFactorOverride>().0;
+
+    let mut resolution = WindowResolution::new(800., 600.);
+    resolution.set_scale_factor_override(scale_factor_override);
+    // The override keeps the physical size, put the logical size back.
+    resolution.set(800., 600.);
+
+    Window {
+        title: branding.title.clone(),
+        resizable: false,
+        resolution,
+        canvas: Some("#bevy".to_owned()),
+        desired_maximum_frame_latency: core::num::NonZero::new(1u32),
+        present_mode,
+        ..default()
+    }
+}
+
+fn apply_render_mode(mut commands: Commands, mode: Res<RenderMode>) {
+    commands.insert_resource(mode.winit_settings());
+}
+
+fn apply_scale_factor_override(
+    scale_factor_override: Res<ScaleFactorOverride>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    for mut window in &mut windows {
+        let resolution = &mut window.resolution;
+        let (width, height) = (resolution.width(), resolution.height());
+        resolution.set_scale_factor_override(scale_factor_override.0);
+        resolution.set(width, height);
+    }
+}
+
+fn load_window_icon(
+    mut commands: Commands,
+    branding: Res<WindowBranding>,
+    asset_server: Res<AssetServer>,
+) {
+    if let Some(path) = &branding.icon_path {
+        commands.insert_resource(WindowIcon(asset_server.load(path.clone())));
+    }
+}
+
+// Winit wants raw RGBA pixels, so wait for the image to load and for the OS
+// window to exist, then hand it over once. A missing or broken icon only logs a
+// warning.
+fn set_window_icon(
+    mut commands: Commands,
+    icon: Res<WindowIcon>,
+    images: Res<Assets<Image>>,
+    asset_server: Res<AssetServer>,
+    windows: NonSend<WinitWindows>,
+) {
+    if let LoadState::Failed(error) = asset_server.load_state(&icon.0) {
+        warn!("Failed to load window icon: {error}");
+        commands.remove_resource::<WindowIcon>();
+        return;
+    }
+
+    let Some(image) = images.get(&icon.0) else {
+        return;
+    };
+
+    if windows.windows.is_empty() {
+        return;
+    }
+
+    let rgba = match image.clone().try_into_dynamic() {
+        Ok(dynamic) => dynamic.into_rgba8(),
+        Err(error) => {
+            warn!("Unsupported window icon format: {error}");
+            commands.remove_resource::<WindowIcon>();
+            return;
+        }
+    };
+    let (width, height) = rgba.dimensions();
+
+    let icon = match Icon::from_rgba(rgba.into_raw(), width, height) {
+        Ok(icon) => icon,
+        Err(error) => {
+            warn!("Invalid window icon: {error}");
+            commands.remove_resource::<WindowIcon>();
+            return;
+        }
+    };
+
+    for window in windows.windows.values() {
+        window.set_window_icon(Some(icon.clone()));
+    }
+    commands.remove_resource::<WindowIcon>();
+}
+
+fn toggle_vsync(mut config: ResMut<PresentModeConfig>) {
+    config.toggle_vsync();
+}
+
+fn apply_present_mode(
+    config: Res<PresentModeConfig>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    for mut window in &mut windows {
+        window.present_mode = config.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::TypeId;
+
+    use super::*;
+
+    #[test]
+    fn config_types_are_reflected() {
+        let mut app = App::new();
+        init_config(&mut app);
+
+        let registry = app.world().resource::<AppTypeRegistry>().read();
+        assert!(registry.contains(TypeId::of::<PresentModeConfig>()));
+    }
+
+    #[test]
+    fn primary_window_uses_branding_title() {
+        let mut app = App::new();
+        init_config(&mut app);
+        app.insert_resource(WindowBranding {
+            title: "Flocking".into(),
+            icon_path: None,
+        });
+
+        assert_eq!(primary_window(app.world()).title, "Flocking");
+    }
+
+    #[test]
+    fn toggle_vsync_flips_between_vsync_and_immediate() {
+        let mut config = PresentModeConfig::default();
+
+        config.toggle_vsync();
+        assert_eq!(config.0, PresentMode::Immediate);
+
+        config.toggle_vsync();
+        assert_eq!(config.0, PresentMode::AutoVsync);
+    }
+
+    #[test]
+    fn changing_config_updates_primary_window() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<PresentModeConfig>()
+            .add_systems(
+                Update,
+                apply_present_mode.run_if(resource_changed::<PresentModeConfig>),
+            );
+        let window = app
+            .world_mut()
+            .spawn((Window::default(), PrimaryWindow))
+            .id();
+
+        app.update();
+        assert_eq!(
+            app.world().get::<Window>(window).unwrap().present_mode,
+            PresentMode::AutoVsync
+        );
+
+        app.world_mut().resource_mut::<PresentModeConfig>().0 = PresentMode::Immediate;
+        app.update();
+        assert_eq!(
+            app.world().get::<Window>(window).unwrap().present_mode,
+            PresentMode::Immediate
+        );
+    }
+
+    #[test]
+    fn render_mode_applies_winit_settings() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(RenderMode::Reactive)
+            .add_systems(
+                Update,
+                apply_render_mode.run_if(resource_changed::<RenderMode>),
+            );
+
+        app.update();
+
+        let settings = app.world().resource::<WinitSettings>();
+        let desktop = WinitSettings::desktop_app();
+        assert_eq!(settings.focused_mode, desktop.focused_mode);
+        assert_eq!(settings.unfocused_mode, desktop.unfocused_mode);
+        assert_ne!(settings.focused_mode, WinitSettings::game().focused_mode);
+    }
+
+    #[test]
+    fn scale_factor_override_is_applied_to_the_primary_window() {
+        let mut app = App::new();
+        init_config(&mut app);
+        app.insert_resource(ScaleFactorOverride(Some(2.0)));
+
+        let window = primary_window(app.world());
+        assert_eq!(window.resolution.scale_factor_override(), Some(2.0));
+        // Logical size, and so world coordinates, stay the same.
+        assert_eq!(window.resolution.width(), 800.0);
+        assert_eq!(window.resolution.height(), 600.0);
+    }
+
+    #[test]
+    fn changing_scale_factor_override_updates_primary_window() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<ScaleFactorOverride>()
+            .add_systems(
+                Update,
+                apply_scale_factor_override.run_if(resource_changed::<ScaleFactorOverride>),
+            );
+        let window = app
+            .world_mut()
+            .spawn((Window::default(), PrimaryWindow))
+            .id();
+
+        app.world_mut().resource_mut::<ScaleFactorOverride>().0 = Some(1.5);
+        app.update();
+
+        let resolution = &app.world().get::<Window>(window).unwrap().resolution;
+        assert_eq!(resolution.scale_factor_override(), Some(1.5));
+        assert_eq!(resolution.width(), Window::default().resolution.width());
+    }
 }